@@ -0,0 +1,26 @@
+//! File-like objects addressable through a task's file-descriptor table
+
+mod pipe;
+mod stdio;
+
+use alloc::vec::Vec;
+
+pub use pipe::make_pipe;
+pub use stdio::{Stdin, Stdout};
+
+/// A file-like object that a task can read from and write to by fd.
+///
+/// `read`/`write` take the per-page slices produced by
+/// [`crate::mm::translated_byte_buffer`] rather than a single contiguous
+/// buffer, so implementors must be able to fill/drain a buffer split across
+/// several physical pages.
+pub trait File: Send + Sync {
+    /// Whether this fd may be passed to `read`
+    fn readable(&self) -> bool;
+    /// Whether this fd may be passed to `write`
+    fn writable(&self) -> bool;
+    /// Read into `buf`, returning the number of bytes read
+    fn read(&self, buf: Vec<&'static mut [u8]>) -> usize;
+    /// Write from `buf`, returning the number of bytes written
+    fn write(&self, buf: Vec<&'static mut [u8]>) -> usize;
+}
@@ -0,0 +1,172 @@
+//! A ring-buffer-backed pipe, used to implement `sys_pipe`
+
+use super::File;
+use crate::sync::UPSafeCell;
+use crate::task::suspend_current_and_run_next;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+const RING_BUFFER_SIZE: usize = 32;
+
+#[derive(Copy, Clone, PartialEq)]
+enum RingBufferStatus {
+    Full,
+    Empty,
+    Normal,
+}
+
+/// The shared buffer backing a pipe's read and write ends
+pub struct PipeRingBuffer {
+    arr: [u8; RING_BUFFER_SIZE],
+    head: usize,
+    tail: usize,
+    status: RingBufferStatus,
+    write_end: Option<Weak<Pipe>>,
+}
+
+impl PipeRingBuffer {
+    pub fn new() -> Self {
+        Self {
+            arr: [0; RING_BUFFER_SIZE],
+            head: 0,
+            tail: 0,
+            status: RingBufferStatus::Empty,
+            write_end: None,
+        }
+    }
+
+    pub fn set_write_end(&mut self, write_end: &Arc<Pipe>) {
+        self.write_end = Some(Arc::downgrade(write_end));
+    }
+
+    pub fn write_byte(&mut self, byte: u8) {
+        self.status = RingBufferStatus::Normal;
+        self.arr[self.tail] = byte;
+        self.tail = (self.tail + 1) % RING_BUFFER_SIZE;
+        if self.tail == self.head {
+            self.status = RingBufferStatus::Full;
+        }
+    }
+
+    pub fn read_byte(&mut self) -> u8 {
+        self.status = RingBufferStatus::Normal;
+        let c = self.arr[self.head];
+        self.head = (self.head + 1) % RING_BUFFER_SIZE;
+        if self.head == self.tail {
+            self.status = RingBufferStatus::Empty;
+        }
+        c
+    }
+
+    pub fn available_read(&self) -> usize {
+        if self.status == RingBufferStatus::Empty {
+            0
+        } else if self.tail > self.head {
+            self.tail - self.head
+        } else {
+            self.tail + RING_BUFFER_SIZE - self.head
+        }
+    }
+
+    pub fn available_write(&self) -> usize {
+        if self.status == RingBufferStatus::Full {
+            0
+        } else {
+            RING_BUFFER_SIZE - self.available_read()
+        }
+    }
+
+    /// Whether every write end referencing this buffer has been dropped
+    pub fn all_write_ends_closed(&self) -> bool {
+        self.write_end.as_ref().unwrap().upgrade().is_none()
+    }
+}
+
+/// One end (read-only or write-only) of a pipe
+pub struct Pipe {
+    readable: bool,
+    writable: bool,
+    buffer: Arc<UPSafeCell<PipeRingBuffer>>,
+}
+
+impl Pipe {
+    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: true,
+            writable: false,
+            buffer,
+        }
+    }
+
+    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: false,
+            writable: true,
+            buffer,
+        }
+    }
+}
+
+/// Allocate a fresh ring buffer and return its (read_end, write_end) pair
+pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new()) });
+    let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone()));
+    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone()));
+    buffer.exclusive_access().set_write_end(&write_end);
+    (read_end, write_end)
+}
+
+impl File for Pipe {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+
+    fn writable(&self) -> bool {
+        self.writable
+    }
+
+    fn read(&self, buf: Vec<&'static mut [u8]>) -> usize {
+        assert!(self.readable);
+        let mut read_size = 0usize;
+        for slice in buf {
+            for byte_ref in slice.iter_mut() {
+                loop {
+                    let mut ring_buffer = self.buffer.exclusive_access();
+                    if ring_buffer.available_read() > 0 {
+                        *byte_ref = ring_buffer.read_byte();
+                        read_size += 1;
+                        break;
+                    } else {
+                        if ring_buffer.all_write_ends_closed() {
+                            return read_size;
+                        }
+                        drop(ring_buffer);
+                        suspend_current_and_run_next();
+                    }
+                }
+            }
+        }
+        read_size
+    }
+
+    fn write(&self, buf: Vec<&'static mut [u8]>) -> usize {
+        assert!(self.writable);
+        let mut write_size = 0usize;
+        for slice in buf {
+            for &byte in slice.iter() {
+                loop {
+                    let mut ring_buffer = self.buffer.exclusive_access();
+                    if ring_buffer.available_write() > 0 {
+                        ring_buffer.write_byte(byte);
+                        write_size += 1;
+                        break;
+                    } else {
+                        drop(ring_buffer);
+                        suspend_current_and_run_next();
+                    }
+                }
+            }
+        }
+        write_size
+    }
+}
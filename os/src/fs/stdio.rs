@@ -0,0 +1,69 @@
+//! Console-backed stdin/stdout file objects
+
+use super::File;
+use crate::sbi::console_getchar;
+use crate::task::suspend_current_and_run_next;
+use alloc::vec::Vec;
+
+/// Standard input, fd 0
+pub struct Stdin;
+
+/// Standard output, fd 1 (and fd 2, used as stderr)
+pub struct Stdout;
+
+impl File for Stdin {
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn read(&self, mut user_buf: Vec<&'static mut [u8]>) -> usize {
+        assert_eq!(
+            user_buf.iter().map(|s| s.len()).sum::<usize>(),
+            1,
+            "Only support reading a single character from stdin"
+        );
+        let mut c: usize;
+        loop {
+            c = console_getchar();
+            if c == 0 {
+                suspend_current_and_run_next();
+                continue;
+            } else {
+                break;
+            }
+        }
+        user_buf[0][0] = c as u8;
+        1
+    }
+
+    fn write(&self, _user_buf: Vec<&'static mut [u8]>) -> usize {
+        panic!("Cannot write to stdin!");
+    }
+}
+
+impl File for Stdout {
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read(&self, _user_buf: Vec<&'static mut [u8]>) -> usize {
+        panic!("Cannot read from stdout!");
+    }
+
+    fn write(&self, user_buf: Vec<&'static mut [u8]>) -> usize {
+        let mut total = 0;
+        for slice in user_buf.iter() {
+            print!("{}", core::str::from_utf8(slice).unwrap());
+            total += slice.len();
+        }
+        total
+    }
+}
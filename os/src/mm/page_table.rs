@@ -0,0 +1,69 @@
+//! Cross-page-safe translation and copying between user and kernel space
+
+use super::{PageTable, VirtAddr};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+/// Translate the user-space byte range `[ptr, ptr + len)` under `token`'s page
+/// table into a list of mutable byte slices, one per physical page the range
+/// touches, in order.
+///
+/// This lets callers operate on a buffer that straddles a page boundary
+/// without assuming it is physically contiguous.
+pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&'static mut [u8]> {
+    let page_table = PageTable::from_token(token);
+    let mut start = ptr as usize;
+    let end = start + len;
+    let mut v = Vec::new();
+    while start < end {
+        let start_va = VirtAddr::from(start);
+        let mut vpn = start_va.floor();
+        let ppn = page_table.translate(vpn).unwrap().ppn();
+        vpn.step();
+        let mut end_va: VirtAddr = vpn.into();
+        end_va = end_va.min(VirtAddr::from(end));
+        if end_va.page_offset() == 0 {
+            v.push(&mut ppn.get_bytes_array()[start_va.page_offset()..]);
+        } else {
+            v.push(&mut ppn.get_bytes_array()[start_va.page_offset()..end_va.page_offset()]);
+        }
+        start = end_va.into();
+    }
+    v
+}
+
+/// Read a NUL-terminated string out of user space, one byte at a time
+/// through [`translated_byte_buffer`] so it is safe even if the string
+/// straddles a page boundary.
+pub fn translated_str(token: usize, ptr: *const u8) -> String {
+    let mut string = String::new();
+    let mut va = ptr as usize;
+    loop {
+        let ch = translated_byte_buffer(token, va as *const u8, 1)[0][0];
+        if ch == 0 {
+            break;
+        }
+        string.push(ch as char);
+        va += 1;
+    }
+    string
+}
+
+/// Copy `value` into the user-space pointer `dst`, scattering the write
+/// across physical pages if `T` straddles a page boundary.
+///
+/// Unlike a plain `*dst = value` through a single translated address, this is
+/// safe even when `size_of::<T>()` bytes starting at `dst` are not backed by a
+/// single physical frame.
+pub fn copy_to_user<T>(token: usize, dst: *mut T, value: &T) {
+    let size = size_of::<T>();
+    let src = unsafe { core::slice::from_raw_parts(value as *const T as *const u8, size) };
+    let buffers = translated_byte_buffer(token, dst as *const u8, size);
+    let mut copied = 0;
+    for buffer in buffers {
+        let len = buffer.len();
+        buffer.copy_from_slice(&src[copied..copied + len]);
+        copied += len;
+    }
+}
@@ -0,0 +1,87 @@
+//! File-descriptor-based I/O syscalls
+
+use crate::fs::make_pipe;
+use crate::mm::{copy_to_user, translated_byte_buffer};
+use crate::task::{current_task, current_user_token};
+
+/// read up to `len` bytes from fd `fd` into the user buffer `buf`
+pub fn sys_read(fd: usize, buf: *mut u8, len: usize) -> isize {
+    trace!("kernel: sys_read");
+    let token = current_user_token();
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    let file = match inner.fd_table[fd].clone() {
+        Some(file) => file,
+        None => return -1,
+    };
+    drop(inner);
+    if !file.readable() {
+        return -1;
+    }
+    file.read(translated_byte_buffer(token, buf, len)) as isize
+}
+
+/// write up to `len` bytes from the user buffer `buf` to fd `fd`
+pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
+    trace!("kernel: sys_write");
+    let token = current_user_token();
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    let file = match inner.fd_table[fd].clone() {
+        Some(file) => file,
+        None => return -1,
+    };
+    drop(inner);
+    if !file.writable() {
+        return -1;
+    }
+    file.write(translated_byte_buffer(token, buf, len)) as isize
+}
+
+/// duplicate fd `fd` onto the lowest-numbered free fd, returning the new fd
+pub fn sys_dup(fd: usize) -> isize {
+    trace!("kernel: sys_dup");
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return -1;
+    }
+    let new_fd = inner.alloc_fd();
+    inner.fd_table[new_fd] = inner.fd_table[fd].clone();
+    new_fd as isize
+}
+
+/// close fd `fd`
+pub fn sys_close(fd: usize) -> isize {
+    trace!("kernel: sys_close");
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return -1;
+    }
+    inner.fd_table[fd].take();
+    0
+}
+
+/// allocate a pipe, writing its (read_fd, write_fd) pair through `pipe`
+pub fn sys_pipe(pipe: *mut usize) -> isize {
+    trace!("kernel: sys_pipe");
+    let task = current_task().unwrap();
+    let token = current_user_token();
+    let mut inner = task.inner_exclusive_access();
+    let (pipe_read, pipe_write) = make_pipe();
+    let read_fd = inner.alloc_fd();
+    inner.fd_table[read_fd] = Some(pipe_read);
+    let write_fd = inner.alloc_fd();
+    inner.fd_table[write_fd] = Some(pipe_write);
+    drop(inner);
+    copy_to_user(token, pipe, &read_fd);
+    copy_to_user(token, unsafe { pipe.add(1) }, &write_fd);
+    0
+}
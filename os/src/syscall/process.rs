@@ -1,13 +1,16 @@
 //! Process management syscalls
 use crate::{
     config::{MAX_SYSCALL_NUM, PAGE_SIZE},
-    mm::VirtAddr,
+    loader::get_app_data_by_name,
+    mm::{copy_to_user, translated_str},
     task::{
-        change_program_brk, current_task_map, current_task_unmap, exit_current_and_run_next,
-        get_task_info, suspend_current_and_run_next, translate_current_task_addr, TaskStatus,
+        add_task, change_program_brk, current_task, current_task_map, current_task_unmap,
+        current_user_token, exit_current_and_run_next, get_task_info,
+        suspend_current_and_run_next, TaskStatus,
     },
     timer::get_time_us,
 };
+use alloc::sync::Arc;
 
 #[repr(C)]
 #[derive(Debug)]
@@ -27,10 +30,25 @@ pub struct TaskInfo {
     time: usize,
 }
 
+/// Task information, detailed with cumulative per-syscall kernel time
+/// alongside call counts, for profiling which syscalls dominate a task's
+/// kernel time
+#[repr(C)]
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct TaskInfoDetailed {
+    /// Task status in it's life cycle
+    status: TaskStatus,
+    /// (call count, cumulative microseconds) per syscall
+    syscall_times: [(u32, usize); MAX_SYSCALL_NUM],
+    /// Total running time of task
+    time: usize,
+}
+
 /// task exits and submit an exit code
-pub fn sys_exit(_exit_code: i32) -> ! {
+pub fn sys_exit(exit_code: i32) -> ! {
     trace!("kernel: sys_exit");
-    exit_current_and_run_next();
+    exit_current_and_run_next(exit_code);
     panic!("Unreachable in sys_exit!");
 }
 
@@ -41,43 +59,151 @@ pub fn sys_yield() -> isize {
     0
 }
 
-/// YOUR JOB: get time with second and microsecond
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TimeVal`] is splitted by two pages ?
+/// get the pid of the current process
+pub fn sys_getpid() -> isize {
+    trace!("kernel: sys_getpid");
+    current_task().unwrap().getpid() as isize
+}
+
+/// fork the current process, returning the child's pid to the parent and 0
+/// to the child
+pub fn sys_fork() -> isize {
+    trace!("kernel: sys_fork");
+    let current_task = current_task().unwrap();
+    let new_task = current_task.fork();
+    let new_pid = new_task.getpid();
+    // put the return value of the child to 0, since a0 carries the syscall
+    // return value back through trap_return
+    let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
+    trap_cx.x[10] = 0;
+    add_task(new_task);
+    new_pid as isize
+}
+
+/// replace the current process's address space with the ELF at `path`
+pub fn sys_exec(path: *const u8) -> isize {
+    trace!("kernel: sys_exec");
+    let token = current_user_token();
+    let path = translated_str(token, path);
+    if let Some(data) = get_app_data_by_name(&path) {
+        let task = current_task().unwrap();
+        task.exec(data);
+        0
+    } else {
+        -1
+    }
+}
+
+/// spawn the ELF at `path` as a new child, without copying the parent's
+/// address space
+pub fn sys_spawn(path: *const u8) -> isize {
+    trace!("kernel: sys_spawn");
+    let token = current_user_token();
+    let path = translated_str(token, path);
+    if let Some(data) = get_app_data_by_name(&path) {
+        let current_task = current_task().unwrap();
+        let new_task = current_task.spawn(data);
+        let new_pid = new_task.getpid();
+        add_task(new_task);
+        new_pid as isize
+    } else {
+        -1
+    }
+}
+
+/// get time with second and microsecond
 pub fn sys_get_time(ts: *mut TimeVal, _tz: usize) -> isize {
     trace!("kernel: sys_get_time");
     let us = get_time_us();
+    let time_val = TimeVal {
+        sec: us / 1_000_000,
+        usec: us % 1_000_000,
+    };
+    copy_to_user(current_user_token(), ts, &time_val);
+    0
+}
 
-    let ts = ts as usize;
+/// Finish sys_task_info to pass testcases
+pub fn sys_task_info(ti: *mut TaskInfo) -> isize {
+    trace!("kernel: sys_task_info");
 
-    unsafe {
-        *(translate_current_task_addr(VirtAddr::from(ts)).unwrap().0 as *mut TimeVal) = TimeVal {
-            sec: us / 1_000_000,
-            usec: us % 1_000_000,
-        };
+    let (syscall_times, time) = get_task_info();
+    let mut counts = [0u32; MAX_SYSCALL_NUM];
+    for (count, (calls, _)) in counts.iter_mut().zip(syscall_times.iter()) {
+        *count = *calls;
     }
+    let task_info = TaskInfo {
+        status: TaskStatus::Running,
+        syscall_times: counts,
+        time,
+    };
+    copy_to_user(current_user_token(), ti, &task_info);
     0
 }
 
-/// YOUR JOB: Finish sys_task_info to pass testcases
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TaskInfo`] is splitted by two pages ?
-pub fn sys_task_info(ti: *mut TaskInfo) -> isize {
-    trace!("kernel: sys_task_info NOT IMPLEMENTED YET!");
-
-    let info = get_task_info();
-    unsafe {
-        *(translate_current_task_addr(VirtAddr::from(ti as usize))
-            .unwrap()
-            .0 as *mut TaskInfo) = TaskInfo {
-            status: TaskStatus::Running,
-            syscall_times: info.0,
-            time: info.1,
-        };
-    }
+/// Like [`sys_task_info`], but reports cumulative per-syscall time alongside
+/// call counts
+pub fn sys_task_info_detailed(ti: *mut TaskInfoDetailed) -> isize {
+    trace!("kernel: sys_task_info_detailed");
+
+    let (syscall_times, time) = get_task_info();
+    let task_info = TaskInfoDetailed {
+        status: TaskStatus::Running,
+        syscall_times,
+        time,
+    };
+    copy_to_user(current_user_token(), ti, &task_info);
     0
 }
 
+/// wait for a child to exit, reclaiming its resources, and report its exit code
+///
+/// Returns -1 if `pid` does not identify one of the caller's children, -2 if
+/// matching children exist but none have exited yet, and otherwise the pid of
+/// the reaped child.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+    trace!("kernel: sys_waitpid");
+    let task = current_task().unwrap();
+
+    // find a child matching pid (or any child, if pid == -1)
+    let mut inner = task.inner_exclusive_access();
+    if !inner
+        .children
+        .iter()
+        .any(|p| pid == -1 || pid as usize == p.getpid())
+    {
+        return -1;
+    }
+    let pair = inner.children.iter().enumerate().find(|(_, p)| {
+        p.inner_exclusive_access().is_zombie() && (pid == -1 || pid as usize == p.getpid())
+    });
+    if let Some((idx, _)) = pair {
+        let child = inner.children.remove(idx);
+        // confirm this is the only reference left so the child's MemorySet
+        // and KernelStack are actually freed once it drops here
+        assert_eq!(Arc::strong_count(&child), 1);
+        let found_pid = child.getpid();
+        let exit_code = child.inner_exclusive_access().exit_code;
+        copy_to_user(current_user_token(), exit_code_ptr, &exit_code);
+        found_pid as isize
+    } else {
+        -2
+    }
+}
+
+/// set the priority used by the stride scheduler for the current process
+///
+/// Returns -1 and leaves the priority unchanged when `prio < 2`; otherwise
+/// sets the priority and returns `prio`.
+pub fn sys_set_priority(prio: isize) -> isize {
+    trace!("kernel: sys_set_priority");
+    if prio < 2 {
+        return -1;
+    }
+    current_task().unwrap().set_priority(prio as usize);
+    prio
+}
+
 // YOUR JOB: Implement mmap.
 pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
     trace!("kernel: sys_mmap NOT IMPLEMENTED YET!");
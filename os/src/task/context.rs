@@ -0,0 +1,62 @@
+//! Implementation of [`TaskContext`]
+
+use crate::trap::trap_return;
+
+#[repr(C)]
+/// Task context: the callee-saved registers `__switch` preserves across a
+/// context switch
+pub struct TaskContext {
+    /// return address ( e.g. __restore ) of __switch ASM function
+    ra: usize,
+    /// kernel stack pointer of app
+    sp: usize,
+    /// callee saved registers:  s 0..11
+    s: [usize; 12],
+}
+
+/// Entry point a freshly-scheduled kernel thread's context switches into:
+/// run the entry function stashed on the current TCB, then exit the thread
+/// once it returns. Mirrors how `goto_trap_return` hands a user task's first
+/// switch off to `trap_return`.
+fn kernel_thread_entry() -> ! {
+    let task = crate::task::current_task().expect("kernel thread has no current task");
+    let entry = task
+        .inner_exclusive_access()
+        .kernel_thread_entry
+        .take()
+        .expect("kernel thread TCB is missing its entry point");
+    entry();
+    crate::task::exit_current_and_run_next(0);
+    unreachable!("a kernel thread must not resume after exiting");
+}
+
+impl TaskContext {
+    /// init task context
+    pub fn zero_init() -> Self {
+        Self {
+            ra: 0,
+            sp: 0,
+            s: [0; 12],
+        }
+    }
+
+    /// set Task Context{__restore ASM function: trap_return, sp: kernel stack, s: s_0..12}
+    pub fn goto_trap_return(kstack_ptr: usize) -> Self {
+        Self {
+            ra: trap_return as usize,
+            sp: kstack_ptr,
+            s: [0; 12],
+        }
+    }
+
+    /// Build a context for a brand-new kernel thread: `ra` points at
+    /// [`kernel_thread_entry`], which runs the entry function stashed on the
+    /// TCB and exits the thread once it returns.
+    pub fn kthread_init(kstack_ptr: usize) -> Self {
+        Self {
+            ra: kernel_thread_entry as usize,
+            sp: kstack_ptr,
+            s: [0; 12],
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! The ready queue, scheduled by stride
+
+use super::TaskControlBlock;
+use crate::sync::UPSafeCell;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use lazy_static::lazy_static;
+
+/// The stride added to a task as `BIG_STRIDE / priority` each time it runs
+pub const BIG_STRIDE: usize = 0x10000;
+
+/// A FIFO-backed ready queue from which the task with the smallest stride is
+/// picked on every reschedule
+pub struct TaskManager {
+    ready_queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            ready_queue: VecDeque::new(),
+        }
+    }
+
+    pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        self.ready_queue.push_back(task);
+    }
+
+    /// Remove and return the ready task with the smallest stride
+    ///
+    /// `stride` is a wrapping `usize`, so strides are never compared
+    /// directly. Instead, since the scheduler invariant
+    /// `max_stride - min_stride <= BIG_STRIDE` always holds, `a` is treated
+    /// as smaller than `b` when `b.wrapping_sub(a)`, read as a signed value,
+    /// lies in `[0, BIG_STRIDE]` — that stays correct across a wraparound.
+    pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
+        let min_idx = self
+            .ready_queue
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| stride_distance(a.get_stride(), b.get_stride()))
+            .map(|(idx, _)| idx)?;
+        self.ready_queue.remove(min_idx)
+    }
+}
+
+fn stride_distance(a: usize, b: usize) -> core::cmp::Ordering {
+    let diff = b.wrapping_sub(a) as isize;
+    if diff == 0 {
+        core::cmp::Ordering::Equal
+    } else if (0..=BIG_STRIDE as isize).contains(&diff) {
+        core::cmp::Ordering::Less
+    } else {
+        core::cmp::Ordering::Greater
+    }
+}
+
+lazy_static! {
+    pub static ref TASK_MANAGER: UPSafeCell<TaskManager> = unsafe { UPSafeCell::new(TaskManager::new()) };
+}
+
+/// Add a task to the back of the ready queue
+pub fn add_task(task: Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().add(task);
+}
+
+/// Pop the ready task with the smallest stride, if any
+pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+    TASK_MANAGER.exclusive_access().fetch()
+}
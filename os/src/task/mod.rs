@@ -0,0 +1,104 @@
+//! Task management: the ready queue, the processor, and the task control block
+
+mod context;
+mod manager;
+mod processor;
+#[allow(clippy::module_inception)]
+mod task;
+
+use crate::loader::get_app_data_by_name;
+use alloc::sync::Arc;
+use lazy_static::lazy_static;
+
+pub use context::TaskContext;
+pub use manager::add_task;
+pub use processor::{
+    current_task, current_trap_cx, current_user_token, run_tasks, schedule, take_current_task,
+};
+pub use task::{TaskControlBlock, TaskControlBlockInner, TaskStatus};
+
+lazy_static! {
+    /// The always-running init process. Every task orphaned by its parent
+    /// exiting is reparented here, so a future `waitpid(-1, ...)` from
+    /// initproc can still reap it.
+    pub static ref INITPROC: Arc<TaskControlBlock> = Arc::new(TaskControlBlock::new(
+        get_app_data_by_name("initproc").expect("initproc ELF not found"),
+    ));
+}
+
+/// Put the init process on the ready queue; called once at boot
+pub fn add_initproc() {
+    add_task(INITPROC.clone());
+}
+
+/// Give up the CPU, keeping the current task ready to run again later
+pub fn suspend_current_and_run_next() {
+    let task = take_current_task().unwrap();
+
+    let mut inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut inner.task_cx as *mut TaskContext;
+    inner.task_status = TaskStatus::Ready;
+    drop(inner);
+
+    add_task(task);
+    schedule(task_cx_ptr);
+}
+
+/// Mark the current task exited with `exit_code`, reparent its children to
+/// initproc so they can still be reaped, and switch to the next ready task
+pub fn exit_current_and_run_next(exit_code: i32) {
+    let task = take_current_task().unwrap();
+
+    let mut inner = task.inner_exclusive_access();
+    inner.task_status = TaskStatus::Zombie;
+    inner.exit_code = exit_code;
+
+    // reparent every live child to initproc before dropping our own
+    // reference to it, so none of them become permanently unreachable
+    {
+        let mut initproc_inner = INITPROC.inner_exclusive_access();
+        for child in inner.children.iter() {
+            child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+            initproc_inner.children.push(child.clone());
+        }
+    }
+    inner.children.clear();
+
+    drop(inner);
+    drop(task);
+
+    let mut _unused = TaskContext::zero_init();
+    schedule(&mut _unused as *mut _);
+}
+
+/// Get the current task's (per-syscall (count, time), running time)
+pub fn get_task_info() -> ([(u32, usize); crate::config::MAX_SYSCALL_NUM], usize) {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    (inner.task_syscall_times, inner.task_time)
+}
+
+/// Map `[start, start + len)` into the current task's address space
+pub fn current_task_map(start: usize, len: usize, port: usize) -> isize {
+    let task = current_task().unwrap();
+    let start_va = crate::mm::VirtAddr::from(start);
+    let end_va = crate::mm::VirtAddr::from(start + len);
+    task.map_application_space(start_va, end_va, port)
+        .map(|_| 0)
+        .unwrap_or(-1)
+}
+
+/// Unmap `[start, start + len)` from the current task's address space
+pub fn current_task_unmap(start: usize, len: usize) -> isize {
+    let task = current_task().unwrap();
+    let start_va = crate::mm::VirtAddr::from(start);
+    let end_va = crate::mm::VirtAddr::from(start + len);
+    task.unmap_application_space(start_va, end_va)
+        .map(|_| 0)
+        .unwrap_or(-1)
+}
+
+/// Grow or shrink the current task's heap by `size` bytes
+pub fn change_program_brk(size: i32) -> Option<usize> {
+    current_task().unwrap().change_program_brk(size)
+}
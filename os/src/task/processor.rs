@@ -0,0 +1,98 @@
+//! The processor: tracks the currently running task and the idle control
+//! flow that picks and dispatches the next one
+
+use super::manager::{fetch_task, BIG_STRIDE};
+use super::switch::__switch;
+use super::{TaskContext, TaskControlBlock, TaskStatus};
+use crate::sync::UPSafeCell;
+use crate::trap::TrapContext;
+use alloc::sync::Arc;
+use lazy_static::lazy_static;
+
+/// Per-core processor state: the task presently occupying the CPU, plus the
+/// idle loop's own context to switch back into between tasks
+pub struct Processor {
+    current: Option<Arc<TaskControlBlock>>,
+    idle_task_cx: TaskContext,
+}
+
+impl Processor {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            idle_task_cx: TaskContext::zero_init(),
+        }
+    }
+
+    fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
+        &mut self.idle_task_cx as *mut _
+    }
+
+    pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.current.take()
+    }
+
+    pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
+        self.current.as_ref().map(Arc::clone)
+    }
+}
+
+lazy_static! {
+    pub static ref PROCESSOR: UPSafeCell<Processor> = unsafe { UPSafeCell::new(Processor::new()) };
+}
+
+/// The idle control flow: repeatedly fetch the ready task with the smallest
+/// stride and switch into it. Once it switches back here (by yielding or
+/// exiting), charge it the stride pass for the run it just completed.
+pub fn run_tasks() {
+    loop {
+        let mut processor = PROCESSOR.exclusive_access();
+        if let Some(task) = fetch_task() {
+            let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+            let mut task_inner = task.inner_exclusive_access();
+            let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
+            task_inner.task_status = TaskStatus::Running;
+            drop(task_inner);
+            processor.current = Some(Arc::clone(&task));
+            drop(processor);
+            unsafe {
+                __switch(idle_task_cx_ptr, next_task_cx_ptr);
+            }
+            // back in the idle loop: the task above just gave up the CPU,
+            // either by yielding or exiting, so it owes its stride pass
+            let priority = task.get_priority();
+            task.add_stride(BIG_STRIDE / priority);
+        }
+    }
+}
+
+/// Take the current task out of the processor, e.g. when it exits or blocks
+pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().take_current()
+}
+
+/// Get the current task, cloning the `Arc`
+pub fn current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().current()
+}
+
+/// Get the user token of the current task
+pub fn current_user_token() -> usize {
+    current_task().unwrap().get_user_token()
+}
+
+/// Get the trap context of the current task
+pub fn current_trap_cx() -> &'static mut TrapContext {
+    current_task().unwrap().inner_exclusive_access().get_trap_cx()
+}
+
+/// Give up the processor and switch back to the idle control flow in
+/// [`run_tasks`], to be resumed later from `switched_task_cx_ptr`
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+    let mut processor = PROCESSOR.exclusive_access();
+    let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+    drop(processor);
+    unsafe {
+        __switch(switched_task_cx_ptr, idle_task_cx_ptr);
+    }
+}
@@ -2,10 +2,12 @@
 use super::TaskContext;
 use super::{kstack_alloc, pid_alloc, KernelStack, PidHandle};
 use crate::config::{MAX_SYSCALL_NUM, TRAP_CONTEXT_BASE};
+use crate::fs::{File, Stdin, Stdout};
 use crate::mm::{MapPermission, MemorySet, PhysPageNum, VirtAddr, KERNEL_SPACE};
 use crate::sync::UPSafeCell;
 use crate::trap::{trap_handler, TrapContext};
 use alloc::sync::{Arc, Weak};
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefMut;
 
@@ -24,6 +26,16 @@ pub struct TaskControlBlock {
     inner: UPSafeCell<TaskControlBlockInner>,
 }
 
+/// The fd table every freshly-created process starts out with: stdin on fd
+/// 0, stdout on fd 1, and stdout again on fd 2 (stderr)
+fn default_fd_table() -> Vec<Option<Arc<dyn File + Send + Sync>>> {
+    vec![
+        Some(Arc::new(Stdin)),
+        Some(Arc::new(Stdout)),
+        Some(Arc::new(Stdout)),
+    ]
+}
+
 impl TaskControlBlock {
     /// Get the mutable reference of the inner TCB
     pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
@@ -36,7 +48,7 @@ impl TaskControlBlock {
     }
 
     /// Get the TaskInfo of app
-    pub fn taskinfo(&self) -> (TaskStatus, usize, [u32; MAX_SYSCALL_NUM]) {
+    pub fn taskinfo(&self) -> (TaskStatus, usize, [(u32, usize); MAX_SYSCALL_NUM]) {
         let inner = self.inner_exclusive_access();
         inner.taskinfo()
     }
@@ -59,8 +71,9 @@ pub struct TaskControlBlockInner {
     /// Record the running time of the current process
     pub task_time: usize,
 
-    /// Record the number of system calls made by the current process
-    pub task_syscall_times: [u32; MAX_SYSCALL_NUM],
+    /// Record, per syscall id, the number of times it was called and the
+    /// cumulative microseconds spent executing it
+    pub task_syscall_times: [(u32, usize); MAX_SYSCALL_NUM],
 
     /// Application address space
     pub memory_set: MemorySet,
@@ -87,6 +100,14 @@ pub struct TaskControlBlockInner {
 
     /// priority
     pub priority: usize,
+
+    /// File descriptor table, indexed by fd. A `None` slot is a free fd.
+    pub fd_table: Vec<Option<Arc<dyn File + Send + Sync>>>,
+
+    /// Entry point for a kernel thread, stashed here so the trampoline
+    /// `TaskContext::kthread_init` schedules into can find and run it. `None`
+    /// for ordinary user tasks.
+    pub kernel_thread_entry: Option<fn()>,
 }
 
 impl TaskControlBlockInner {
@@ -110,13 +131,16 @@ impl TaskControlBlockInner {
     }
 
     /// get the TaskInfo for sys_taskinfo
-    pub fn taskinfo(&self) -> (TaskStatus, usize, [u32; MAX_SYSCALL_NUM]) {
+    pub fn taskinfo(&self) -> (TaskStatus, usize, [(u32, usize); MAX_SYSCALL_NUM]) {
         (self.task_status, self.task_time, self.task_syscall_times)
     }
 
-    /// Modify syscall times based on syscall_id
-    pub fn increase_syscall_times(&mut self, syscall_id: usize) {
-        self.task_syscall_times[syscall_id] += 1;
+    /// Record one more call to `syscall_id`, accumulating the microseconds
+    /// it took alongside the call count
+    pub fn increase_syscall_times(&mut self, syscall_id: usize, elapsed_us: usize) {
+        let entry = &mut self.task_syscall_times[syscall_id];
+        entry.0 += 1;
+        entry.1 += elapsed_us;
     }
 
     /// get the trap context
@@ -132,6 +156,16 @@ impl TaskControlBlockInner {
     pub fn is_zombie(&self) -> bool {
         self.task_status == TaskStatus::Zombie
     }
+
+    /// Find the lowest-numbered free fd, growing the table if none is free
+    pub fn alloc_fd(&mut self) -> usize {
+        if let Some(fd) = (0..self.fd_table.len()).find(|fd| self.fd_table[*fd].is_none()) {
+            fd
+        } else {
+            self.fd_table.push(None);
+            self.fd_table.len() - 1
+        }
+    }
 }
 
 impl TaskControlBlock {
@@ -160,7 +194,7 @@ impl TaskControlBlock {
                     task_cx: TaskContext::goto_trap_return(kernel_stack_top),
                     task_status: TaskStatus::Ready,
                     task_time: 0,
-                    task_syscall_times: [0; MAX_SYSCALL_NUM],
+                    task_syscall_times: [(0, 0); MAX_SYSCALL_NUM],
                     memory_set,
                     parent: None,
                     children: Vec::new(),
@@ -169,6 +203,8 @@ impl TaskControlBlock {
                     program_brk: user_sp,
                     stride: 0,
                     priority: 16,
+                    fd_table: default_fd_table(),
+                    kernel_thread_entry: None,
                 })
             },
         };
@@ -227,6 +263,11 @@ impl TaskControlBlock {
         let pid_handle = pid_alloc();
         let kernel_stack = kstack_alloc();
         let kernel_stack_top = kernel_stack.get_top();
+        // copy fd table, so the child inherits every fd the parent has open
+        let mut new_fd_table: Vec<Option<Arc<dyn File + Send + Sync>>> = Vec::new();
+        for fd in parent_inner.fd_table.iter() {
+            new_fd_table.push(fd.clone());
+        }
         let task_control_block = Arc::new(TaskControlBlock {
             pid: pid_handle,
             kernel_stack,
@@ -237,7 +278,7 @@ impl TaskControlBlock {
                     task_cx: TaskContext::goto_trap_return(kernel_stack_top),
                     task_status: TaskStatus::Ready,
                     task_time: 0,
-                    task_syscall_times: [0; MAX_SYSCALL_NUM],
+                    task_syscall_times: [(0, 0); MAX_SYSCALL_NUM],
                     memory_set,
                     parent: Some(Arc::downgrade(self)),
                     children: Vec::new(),
@@ -246,6 +287,8 @@ impl TaskControlBlock {
                     program_brk: parent_inner.program_brk,
                     stride: 0,
                     priority: 16,
+                    fd_table: new_fd_table,
+                    kernel_thread_entry: None,
                 })
             },
         });
@@ -261,8 +304,8 @@ impl TaskControlBlock {
         // ---- release parent PCB
     }
 
-    /// parent process spawn the cild process
-    pub fn spwan(self: &Arc<Self>, elf_data: &[u8]) -> Arc<Self> {
+    /// parent process spawn the child process
+    pub fn spawn(self: &Arc<Self>, elf_data: &[u8]) -> Arc<Self> {
         let mut parent_inner = self.inner_exclusive_access();
         let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
         let trap_cx_ppn = memory_set
@@ -283,7 +326,7 @@ impl TaskControlBlock {
                     task_cx: TaskContext::goto_trap_return(kernel_stack_top),
                     task_status: TaskStatus::Ready,
                     task_time: 0,
-                    task_syscall_times: [0; MAX_SYSCALL_NUM],
+                    task_syscall_times: [(0, 0); MAX_SYSCALL_NUM],
                     memory_set,
                     parent: Some(Arc::downgrade(self)),
                     children: Vec::new(),
@@ -292,6 +335,8 @@ impl TaskControlBlock {
                     program_brk: user_sp,
                     stride: 0,
                     priority: 16,
+                    fd_table: default_fd_table(),
+                    kernel_thread_entry: None,
                 })
             },
         });
@@ -400,9 +445,49 @@ impl TaskControlBlock {
             None
         }
     }
+
+    /// Create a kernel thread running `entry` in the kernel's own address
+    /// space, with no parent and no user-mode image. It is scheduled through
+    /// the same ready queue as user tasks, letting the kernel run background
+    /// work (e.g. deferred frame reclamation) as a first-class task.
+    pub fn new_kernel_thread(entry: fn()) -> Arc<Self> {
+        let memory_set = MemorySet::kernel_copy();
+        // a kernel thread never takes a user trap, so it has no
+        // TRAP_CONTEXT_BASE mapping (kernel_copy's shared address space
+        // doesn't map it per-process the way from_elf/from_existed_user do)
+        // and trap_cx_ppn is simply never read
+        let trap_cx_ppn = PhysPageNum(0);
+        let pid_handle = pid_alloc();
+        let kernel_stack = kstack_alloc();
+        let kernel_stack_top = kernel_stack.get_top();
+        Arc::new(Self {
+            pid: pid_handle,
+            kernel_stack,
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    trap_cx_ppn,
+                    base_size: 0,
+                    task_cx: TaskContext::kthread_init(kernel_stack_top),
+                    task_status: TaskStatus::Ready,
+                    task_time: 0,
+                    task_syscall_times: [(0, 0); MAX_SYSCALL_NUM],
+                    memory_set,
+                    parent: None,
+                    children: Vec::new(),
+                    exit_code: 0,
+                    heap_bottom: 0,
+                    program_brk: 0,
+                    stride: 0,
+                    priority: 16,
+                    fd_table: Vec::new(),
+                    kernel_thread_entry: Some(entry),
+                })
+            },
+        })
+    }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 /// task status: UnInit, Ready, Running, Exited
 pub enum TaskStatus {
     /// uninitialized
@@ -0,0 +1,127 @@
+//! Trap handling: entry from user space, syscall dispatch, and return
+
+mod context;
+
+use crate::config::{TRAMPOLINE, TRAP_CONTEXT_BASE};
+use crate::syscall::syscall;
+use crate::task::{current_task, current_trap_cx, current_user_token, exit_current_and_run_next};
+use crate::timer::{get_time_us, set_next_trigger};
+use core::arch::asm;
+use riscv::register::{
+    mtvec::TrapMode,
+    scause::{self, Exception, Interrupt, Trap},
+    sie, stval, stvec,
+};
+
+pub use context::TrapContext;
+
+/// Route traps taken from user mode through the trampoline page
+fn set_user_trap_entry() {
+    unsafe {
+        stvec::write(TRAMPOLINE as usize, TrapMode::Direct);
+    }
+}
+
+/// Route traps taken while already in the kernel here directly, since the
+/// trampoline is only mapped into user address spaces
+fn set_kernel_trap_entry() {
+    unsafe {
+        stvec::write(trap_from_kernel as usize, TrapMode::Direct);
+    }
+}
+
+/// Enable the timer interrupt, used to pre-empt long-running tasks
+pub fn enable_timer_interrupt() {
+    unsafe {
+        sie::set_stimer();
+    }
+}
+
+/// Handle a trap from user space
+#[no_mangle]
+pub fn trap_handler() -> ! {
+    set_kernel_trap_entry();
+    let scause = scause::read();
+    let stval = stval::read();
+    match scause.cause() {
+        Trap::Exception(Exception::UserEnvCall) => {
+            let mut cx = current_trap_cx();
+            cx.sepc += 4;
+            let syscall_id = cx.x[17];
+            let args = [cx.x[10], cx.x[11], cx.x[12]];
+
+            // time the syscall so its (count, time) slot in the TCB reflects
+            // how long it actually took, not just that it was called
+            let start_us = get_time_us();
+            let result = syscall(syscall_id, args);
+            let elapsed_us = get_time_us() - start_us;
+            current_task()
+                .unwrap()
+                .inner_exclusive_access()
+                .increase_syscall_times(syscall_id, elapsed_us);
+
+            // a syscall (e.g. exec) may have replaced the trap context, so
+            // re-fetch it rather than reusing the stale `cx` above
+            cx = current_trap_cx();
+            cx.x[10] = result as usize;
+        }
+        Trap::Exception(Exception::StoreFault)
+        | Trap::Exception(Exception::StorePageFault)
+        | Trap::Exception(Exception::InstructionFault)
+        | Trap::Exception(Exception::InstructionPageFault)
+        | Trap::Exception(Exception::LoadFault)
+        | Trap::Exception(Exception::LoadPageFault) => {
+            error!(
+                "[kernel] trap_handler: bad memory access, stval = {:#x}, killing the app",
+                stval
+            );
+            exit_current_and_run_next(-2);
+        }
+        Trap::Exception(Exception::IllegalInstruction) => {
+            error!("[kernel] IllegalInstruction in application, killing it.");
+            exit_current_and_run_next(-3);
+        }
+        Trap::Interrupt(Interrupt::SupervisorTimer) => {
+            set_next_trigger();
+            crate::task::suspend_current_and_run_next();
+        }
+        _ => {
+            panic!(
+                "Unsupported trap {:?}, stval = {:#x}!",
+                scause.cause(),
+                stval
+            );
+        }
+    }
+    trap_return();
+}
+
+/// Return to user space once a trap has been handled, jumping through the
+/// trampoline page's `__restore`
+#[no_mangle]
+pub fn trap_return() -> ! {
+    set_user_trap_entry();
+    let trap_cx_ptr = TRAP_CONTEXT_BASE;
+    let user_satp = current_user_token();
+    extern "C" {
+        fn __alltraps();
+        fn __restore();
+    }
+    let restore_va = __restore as usize - __alltraps as usize + TRAMPOLINE;
+    unsafe {
+        asm!(
+            "fence.i",
+            "jr {restore_va}",
+            restore_va = in(reg) restore_va,
+            in("a0") trap_cx_ptr,
+            in("a1") user_satp,
+            options(noreturn)
+        );
+    }
+}
+
+/// A trap taken while already in the kernel is always a bug
+#[no_mangle]
+pub fn trap_from_kernel() -> ! {
+    panic!("a trap from kernel!");
+}